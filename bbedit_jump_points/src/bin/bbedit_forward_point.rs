@@ -0,0 +1,45 @@
+//! Re-advance the front BBEdit window to a point we stepped back past.
+
+use std::process::exit;
+use bbedit_jump_points::*;
+use chrono::{Duration, Utc};
+pub(crate) use std::process::Command;
+
+fn run() -> Result<(), JumpError> {
+    let bbedit_pid = pid_for_asn(front_app_asn()?)?;
+
+    let max_age = Duration::hours(1);
+
+    let points_pathbuf = get_points_pathbuf();
+    let points_path = points_pathbuf.as_path();
+
+    let now = Utc::now();
+    let mut points_data = get_points(points_path, now - max_age)?;
+
+    if let Some(stacks) = points_data.get_mut(&bbedit_pid) {
+        if let Some(next_point) = stacks.forward.pop() {
+            // Re-advancing puts the point back on the back stack so it
+            // can be popped again.
+            stacks.back.push(next_point.clone());
+            save_points(points_path, points_data)?;
+
+            let _ = Command::new("/usr/local/bin/bbedit")
+                .arg(format!("+{}:{}", &next_point.line, &next_point.column))
+                .arg(&next_point.filename)
+                .output();
+
+            eprintln!("advanced: {} => {:?}", bbedit_pid, next_point);
+            return Ok(());
+        }
+    }
+
+    eprintln!("no more forward points for pid {}", bbedit_pid);
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("bbedit_forward_point: {err}");
+        exit(1);
+    }
+}
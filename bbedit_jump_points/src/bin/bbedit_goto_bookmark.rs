@@ -0,0 +1,55 @@
+//! Jump the front BBEdit window directly to a named bookmark.
+
+use std::env;
+use std::process::exit;
+use bbedit_jump_points::*;
+use chrono::{Duration, Utc};
+pub(crate) use std::process::Command;
+
+fn run(label: String) -> Result<(), JumpError> {
+    let bbedit_pid = pid_for_asn(front_app_asn()?)?;
+
+    let max_age = Duration::hours(1);
+
+    let points_pathbuf = get_points_pathbuf();
+    let points_path = points_pathbuf.as_path();
+
+    let now = Utc::now();
+    let points_data = get_points(points_path, now - max_age)?;
+
+    // Bookmarks are stored by name, independent of stack position.
+    let bookmark = points_data
+        .get(&bbedit_pid)
+        .and_then(|stacks| stacks.bookmarks.get(&label));
+
+    match bookmark {
+        Some(point) => {
+            let _ = Command::new("/usr/local/bin/bbedit")
+                .arg(format!("+{}:{}", point.line, point.column))
+                .arg(&point.filename)
+                .output();
+
+            eprintln!("jumped to {:?}: {:?}", label, point);
+            Ok(())
+        }
+        None => {
+            eprintln!("no bookmark {:?} for pid {}", label, bbedit_pid);
+            Ok(())
+        }
+    }
+}
+
+fn main() {
+    let label = match env::args().nth(1) {
+        Some(label) => label,
+        None => {
+            eprintln!("usage: bbedit_goto_bookmark <name>");
+            exit(2);
+        }
+    };
+
+    if let Err(err) = run(label) {
+        eprintln!("bbedit_goto_bookmark: {err}");
+        exit(1);
+    }
+}
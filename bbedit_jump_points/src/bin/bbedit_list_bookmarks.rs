@@ -0,0 +1,32 @@
+//! Print the named bookmarks saved for the front BBEdit window.
+
+use std::process::exit;
+use bbedit_jump_points::*;
+use chrono::{Duration, Utc};
+
+fn run() -> Result<(), JumpError> {
+    let bbedit_pid = pid_for_asn(front_app_asn()?)?;
+
+    let max_age = Duration::hours(1);
+
+    let points_pathbuf = get_points_pathbuf();
+    let points_path = points_pathbuf.as_path();
+
+    let now = Utc::now();
+    let points_data = get_points(points_path, now - max_age)?;
+
+    if let Some(stacks) = points_data.get(&bbedit_pid) {
+        for (label, point) in &stacks.bookmarks {
+            println!("{}\t{}:{}:{}", label, point.filename, point.line, point.column);
+        }
+    }
+
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("bbedit_list_bookmarks: {err}");
+        exit(1);
+    }
+}
@@ -1,11 +1,12 @@
 //! Load the front BBEdit window's previous jump point, and return to it.
 
+use std::process::exit;
 use bbedit_jump_points::*;
 use chrono::{Duration, Utc};
 pub(crate) use std::process::Command;
 
-fn main() {
-    let bbedit_pid = pid_for_asn(front_app_asn());
+fn run() -> Result<(), JumpError> {
+    let bbedit_pid = pid_for_asn(front_app_asn()?)?;
 
     let max_age = Duration::hours(1);
 
@@ -13,19 +14,32 @@ fn main() {
     let points_path = points_pathbuf.as_path();
 
     let now = Utc::now();
-    let mut points_data = get_points(points_path, now - max_age);
-
-    if let Some(points) = points_data.get_mut(&bbedit_pid) {
-        let last_point = points.pop().unwrap();
-        save_points(points_path, points_data);
+    let mut points_data = get_points(points_path, now - max_age)?;
+
+    if let Some(stacks) = points_data.get_mut(&bbedit_pid) {
+        if let Some(last_point) = stacks.back.pop() {
+            // Stepping back makes this point re-advanceable via the
+            // forward binary.
+            stacks.forward.push(last_point.clone());
+            save_points(points_path, points_data)?;
+
+            let _ = Command::new("/usr/local/bin/bbedit")
+                .arg(format!("+{}:{}", &last_point.line, &last_point.column))
+                .arg(&last_point.filename)
+                .output();
+
+            eprintln!("popped: {} => {:?}", bbedit_pid, last_point);
+            return Ok(());
+        }
+    }
 
-        let _ = Command::new("/usr/local/bin/bbedit")
-            .arg(format!("+{}:{}", &last_point.line, &last_point.column))
-            .arg(&last_point.filename)
-            .output();
+    eprintln!("no more return points for pid {}", bbedit_pid);
+    Ok(())
+}
 
-        eprintln!("popped: {} => {:?}", bbedit_pid, last_point);
-    } else {
-        eprintln!("no more return points for pid {}", bbedit_pid);
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("bbedit_pop_point: {err}");
+        exit(1);
     }
 }
@@ -1,11 +1,12 @@
 //! Get the front BBEdit window's current location, and store it.
 
 use std::env;
+use std::process::exit;
 use bbedit_jump_points::*;
 use chrono::{Duration, Utc};
 
-fn main() {
-    let bbedit_pid = pid_for_asn(front_app_asn());
+fn run() -> Result<(), JumpError> {
+    let bbedit_pid = pid_for_asn(front_app_asn()?)?;
 
     let max_age = Duration::hours(1);
 
@@ -13,25 +14,38 @@ fn main() {
     let points_path = points_pathbuf.as_path();
 
     let now = Utc::now();
-    let mut points_data = get_points(points_path, now - max_age);
+    let mut points_data = get_points(points_path, now - max_age)?;
 
     let new_point = JumpPoint {
-        filename: env::var("BB_DOC_PATH").unwrap(),
-        line: env::var("BB_DOC_SELSTART_LINE")
-            .unwrap()
-            .parse::<i64>()
-            .unwrap(),
-        column: env::var("BB_DOC_SELSTART_COLUMN")
-            .unwrap()
-            .parse::<i64>()
-            .unwrap(),
+        filename: env::var("BB_DOC_PATH")
+            .map_err(|_| JumpError::MissingEnv("BB_DOC_PATH".to_string()))?,
+        line: parse_location("BB_DOC_SELSTART_LINE")?,
+        column: parse_location("BB_DOC_SELSTART_COLUMN")?,
         added: now,
+        label: None,
     };
 
-    if let Some(points) = points_data.get_mut(&bbedit_pid) {
-        points.push(new_point);
-    } else {
-        points_data.insert(bbedit_pid, vec![new_point]);
+    // Recording a new point starts a fresh history branch: it goes onto
+    // the back stack, and any points we could have re-advanced to are no
+    // longer reachable.
+    let stacks = points_data.entry(bbedit_pid).or_default();
+    stacks.back.push(new_point);
+    stacks.forward.clear();
+
+    save_points(points_path, points_data)
+}
+
+/// Read an integer cursor coordinate out of the environment.
+fn parse_location(var: &str) -> Result<i64, JumpError> {
+    env::var(var)
+        .map_err(|_| JumpError::MissingEnv(var.to_string()))?
+        .parse::<i64>()
+        .map_err(|err| JumpError::ParseLocation(format!("{var}: {err}")))
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("bbedit_push_point: {err}");
+        exit(1);
     }
-    save_points(points_path, points_data);
 }
@@ -0,0 +1,58 @@
+//! Record the front BBEdit window's current location under a name.
+
+use std::env;
+use std::process::exit;
+use bbedit_jump_points::*;
+use chrono::{Duration, Utc};
+
+fn run(label: String) -> Result<(), JumpError> {
+    let bbedit_pid = pid_for_asn(front_app_asn()?)?;
+
+    let max_age = Duration::hours(1);
+
+    let points_pathbuf = get_points_pathbuf();
+    let points_path = points_pathbuf.as_path();
+
+    let now = Utc::now();
+    let mut points_data = get_points(points_path, now - max_age)?;
+
+    let new_point = JumpPoint {
+        filename: env::var("BB_DOC_PATH")
+            .map_err(|_| JumpError::MissingEnv("BB_DOC_PATH".to_string()))?,
+        line: parse_location("BB_DOC_SELSTART_LINE")?,
+        column: parse_location("BB_DOC_SELSTART_COLUMN")?,
+        added: now,
+        label: Some(label.clone()),
+    };
+
+    // Bookmarks live in their own map, so saving one can't disturb the
+    // back/forward history, and inserting by name naturally replaces any
+    // earlier bookmark with the same label.
+    let stacks = points_data.entry(bbedit_pid).or_default();
+    stacks.bookmarks.insert(label, new_point);
+
+    save_points(points_path, points_data)
+}
+
+/// Read an integer cursor coordinate out of the environment.
+fn parse_location(var: &str) -> Result<i64, JumpError> {
+    env::var(var)
+        .map_err(|_| JumpError::MissingEnv(var.to_string()))?
+        .parse::<i64>()
+        .map_err(|err| JumpError::ParseLocation(format!("{var}: {err}")))
+}
+
+fn main() {
+    let label = match env::args().nth(1) {
+        Some(label) => label,
+        None => {
+            eprintln!("usage: bbedit_save_bookmark <name>");
+            exit(2);
+        }
+    };
+
+    if let Err(err) = run(label) {
+        eprintln!("bbedit_save_bookmark: {err}");
+        exit(1);
+    }
+}
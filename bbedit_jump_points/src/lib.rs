@@ -6,13 +6,63 @@
 
 #![warn(missing_docs)]
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use directories::ProjectDirs;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
-use std::fs::{create_dir, read, write};
+use std::fs::{create_dir, create_dir_all, read, write};
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use sysinfo::{Pid, System};
+
+/// The ways the jump-point tools can fail.
+///
+/// Failures from the environment, `lsappinfo`, and the points file
+/// surface through one of these variants so the binaries can print a
+/// clean diagnostic and exit non-zero instead of unwinding. Locating the
+/// project data directory still panics if it can't be determined.
+#[derive(Debug)]
+pub enum JumpError {
+    /// A required environment variable wasn't set.
+    MissingEnv(String),
+    /// A BBEdit location value (line or column) wasn't an integer.
+    ParseLocation(String),
+    /// `lsappinfo` didn't return a usable pid for an ASN, including its
+    /// undocumented, human-looking non-`"pid"=` output.
+    PidLookup(String),
+    /// The points file couldn't be parsed as YAML.
+    Yaml(serde_yaml::Error),
+    /// An underlying I/O operation failed.
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for JumpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JumpError::MissingEnv(var) => write!(f, "missing environment variable {var}"),
+            JumpError::ParseLocation(msg) => write!(f, "invalid cursor location: {msg}"),
+            JumpError::PidLookup(msg) => write!(f, "pid lookup failed: {msg}"),
+            JumpError::Yaml(err) => write!(f, "couldn't parse points file: {err}"),
+            JumpError::Io(err) => write!(f, "I/O error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for JumpError {}
+
+impl From<std::io::Error> for JumpError {
+    fn from(err: std::io::Error) -> Self {
+        JumpError::Io(err)
+    }
+}
+
+impl From<serde_yaml::Error> for JumpError {
+    fn from(err: serde_yaml::Error) -> Self {
+        JumpError::Yaml(err)
+    }
+}
 
 /// Represent a cursor location in BBEdit.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -28,14 +78,90 @@ pub struct JumpPoint {
     pub column: i64,
     /// The UTC timestamp when this point was recorded.
     pub added: DateTime<Utc>,
+    /// An optional user-supplied name, set on points stored as named
+    /// bookmarks (see `JumpStacks::bookmarks`). Anonymous breadcrumbs on
+    /// the back/forward stacks leave this `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// A two-stack cursor of jump points for a single window, plus its named
+/// bookmarks.
+///
+/// Recording a point pushes it onto `back` and clears `forward`, just
+/// like navigating in a web browser starts a new history branch.
+/// Popping `back` moves that point onto `forward` so it can be
+/// re-advanced later, and the "forward" binary pops `forward` back onto
+/// `back`. Age-based expiration applies to both stacks.
+///
+/// Named bookmarks live in `bookmarks`, keyed by their label, kept out
+/// of the volatile stacks so a new push or pop can never clobber them.
+/// They're intentional, so they never decay.
+#[derive(Serialize, Clone, Debug, PartialEq, Default)]
+pub struct JumpStacks {
+    /// Points we can return to, most recent last.
+    pub back: Vec<JumpPoint>,
+    /// Points we've stepped back past and can re-advance to.
+    pub forward: Vec<JumpPoint>,
+    /// Named bookmarks, keyed by label.
+    #[serde(default)]
+    pub bookmarks: HashMap<String, JumpPoint>,
+}
+
+impl JumpStacks {
+    /// True when neither stack nor the bookmark set holds any points.
+    pub fn is_empty(&self) -> bool {
+        self.back.is_empty() && self.forward.is_empty() && self.bookmarks.is_empty()
+    }
+}
+
+// Deserialize both the current `{back: [...], forward: [...]}` shape and
+// the original bare `[...]` list, which we treat as the `back` stack
+// with an empty `forward`.
+impl<'de> Deserialize<'de> for JumpStacks {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Legacy(Vec<JumpPoint>),
+            Stacks {
+                #[serde(default)]
+                back: Vec<JumpPoint>,
+                #[serde(default)]
+                forward: Vec<JumpPoint>,
+                #[serde(default)]
+                bookmarks: HashMap<String, JumpPoint>,
+            },
+        }
+
+        Ok(match Raw::deserialize(deserializer)? {
+            Raw::Legacy(back) => JumpStacks {
+                back,
+                forward: vec![],
+                bookmarks: HashMap::new(),
+            },
+            Raw::Stacks {
+                back,
+                forward,
+                bookmarks,
+            } => JumpStacks {
+                back,
+                forward,
+                bookmarks,
+            },
+        })
+    }
 }
 
 /// BBEdit may have multiple windows open at once, each with its own
 /// pid. They shouldn't interfere with each other: If you're writing
 /// Python in 1 window, you don't want to get popped back into some Rust
 /// code in another. The points map's key is an int process ID, and its
-/// value is a vector of JumpPoints.
-pub type PointsMap = HashMap<i32, Vec<JumpPoint>>;
+/// value is that window's `JumpStacks`.
+pub type PointsMap = HashMap<i32, JumpStacks>;
 
 /// Return the path to the `points.yaml` file storing the `PointsMap` data.
 ///
@@ -48,88 +174,186 @@ pub fn get_points_pathbuf() -> PathBuf {
     data_dir.join("points.yaml")
 }
 
+/// A cached subprocess result: its stdout and when we recorded it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct CachedCommand {
+    stdout: String,
+    added: DateTime<Utc>,
+}
+
+/// Return the cache file path for a given command key, ensuring the
+/// cache directory under the project data dir exists.
+fn cache_pathbuf(key: &str) -> PathBuf {
+    let project_dirs = ProjectDirs::from("net", "honeypot", "bbedit_jump_points").unwrap();
+    let cache_dir = project_dirs.data_dir().join("command_cache");
+    let _ = create_dir_all(&cache_dir);
+    cache_dir.join(format!("{key}.yaml"))
+}
+
+/// Run a command, caching its stdout on the filesystem for `ttl`.
+///
+/// The entry is keyed on the full argv plus any `env` pairs the caller
+/// says are relevant, so invocations that would produce different output
+/// don't share a cache entry. If a fresh-enough entry exists we return it
+/// without spawning anything; otherwise we run the command, record its
+/// stdout alongside the current time, and return that. This keeps
+/// binaries bound to frequent editor actions from re-shelling slow tools
+/// on every call.
+pub fn run_cached(
+    argv: &[&str],
+    env: &[(&str, &str)],
+    ttl: Duration,
+) -> Result<String, JumpError> {
+    let mut hasher = DefaultHasher::new();
+    argv.hash(&mut hasher);
+    env.hash(&mut hasher);
+    let cache_path = cache_pathbuf(&format!("{:016x}", hasher.finish()));
+
+    if let Ok(data) = read(&cache_path) {
+        if let Ok(cached) = serde_yaml::from_slice::<CachedCommand>(&data) {
+            if Utc::now() - cached.added < ttl {
+                return Ok(cached.stdout);
+            }
+        }
+    }
+
+    let output = Command::new(argv[0]).args(&argv[1..]).output()?;
+    let stdout = String::from_utf8(output.stdout)
+        .map_err(|err| JumpError::PidLookup(err.to_string()))?;
+
+    let cached = CachedCommand {
+        stdout: stdout.clone(),
+        added: Utc::now(),
+    };
+    let _ = write(&cache_path, serde_yaml::to_string(&cached)?);
+
+    Ok(stdout)
+}
+
 /// Return the ASN of the frontmost open window.
-pub fn front_app_asn() -> String {
-    let visible_process_list_out = String::from_utf8(
-        Command::new("lsappinfo")
-            .arg("visibleProcessList")
-            .output()
-            .unwrap()
-            .stdout,
-    )
-    .unwrap();
+pub fn front_app_asn() -> Result<String, JumpError> {
+    let output = Command::new("lsappinfo")
+        .arg("visibleProcessList")
+        .output()?;
+    let visible_process_list_out = String::from_utf8(output.stdout)
+        .map_err(|err| JumpError::PidLookup(err.to_string()))?;
 
     visible_process_list_out
         .split(' ')
         .next()
-        .unwrap()
-        .to_string()
+        .filter(|asn| !asn.trim().is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| JumpError::PidLookup("lsappinfo returned no visible processes".to_string()))
 }
 
 /// Return the pid of the given ASN.
-pub fn pid_for_asn(asn: String) -> i32 {
+pub fn pid_for_asn(asn: String) -> Result<i32, JumpError> {
     // I'm gonna vent here for a second. If you get this command line
     // wrong, bummer. `lsappinfo` will still exit with status code 0,
     // and will write some text to stdout that looks like an error
     // message to a human, but comprises an undocumented list of
     // possible strings. It does nothing to help you detect that
     // something went badly.
-    let info_out = String::from_utf8(
-        Command::new("lsappinfo")
-            .args(["info", "-only", "pid", &asn])
-            .output()
-            .unwrap()
-            .stdout,
-    )
-    .unwrap();
+    //
+    // A window's ASN->pid mapping is stable for that window's lifetime,
+    // so a short TTL cache eliminates most repeated `lsappinfo` spawns
+    // while staying correct.
+    let info_out = run_cached(
+        &["lsappinfo", "info", "-only", "pid", &asn],
+        &[],
+        Duration::seconds(3),
+    )?;
     for line in info_out.split('\n') {
         if line.starts_with("\"pid\"=") {
-            let pid = line.split('=').nth(1).unwrap();
-            return pid.parse::<i32>().unwrap();
+            let pid = line
+                .split('=')
+                .nth(1)
+                .ok_or_else(|| JumpError::PidLookup(format!("malformed pid line {line:?}")))?;
+            return pid
+                .parse::<i32>()
+                .map_err(|err| JumpError::PidLookup(err.to_string()));
         }
     }
 
-    panic!("Couldn't get the pid for ASN \"{}\"", asn);
+    // No `"pid"=` line means `lsappinfo` emitted one of its undocumented
+    // error-ish strings while still exiting 0. Surface that as a lookup
+    // failure instead of a panic.
+    Err(JumpError::PidLookup(format!(
+        "lsappinfo returned no pid for ASN {asn:?}: {info_out:?}"
+    )))
+}
+
+/// Drop any pid whose process is no longer running.
+///
+/// macOS recycles pids aggressively, so a closed BBEdit window's pid can
+/// end up pointing at some unrelated process. Rather than wait for the
+/// age-based timeout, check each stored pid for a live process (via
+/// `sysinfo`'s syscalls, not by shelling out) and discard the stacks of
+/// any pid that isn't currently alive.
+pub fn retain_live_pids(points_map: &mut PointsMap) {
+    // Only probe the handful of pids we actually have stacks for instead
+    // of enumerating every process on the machine — that full sweep would
+    // cost more than the `lsappinfo` spawn the command cache works to
+    // avoid, undoing that optimization on the hot save path.
+    let mut system = System::new();
+    points_map.retain(|pid, _| {
+        let pid = Pid::from(*pid as usize);
+        system.refresh_process(pid) && system.process(pid).is_some()
+    });
 }
 
 /// Return the previously stored PointsMap.
-pub fn get_points(points_path: &Path, oldest_time: DateTime<Utc>) -> PointsMap {
-    // Get the points file's contents, or an empty string if we can't.
-    let points_data = String::from_utf8(match read(points_path) {
-        Ok(data) => data,
-        Err(_) => vec![],
-    })
-    .unwrap();
+pub fn get_points(points_path: &Path, oldest_time: DateTime<Utc>) -> Result<PointsMap, JumpError> {
+    // Get the points file's contents, or an empty string if we can't. A
+    // missing or unreadable file just means we have nothing saved yet.
+    let points_data = match read(points_path) {
+        Ok(data) => String::from_utf8(data).unwrap_or_default(),
+        Err(_) => String::new(),
+    };
 
-    points_from(points_data, oldest_time)
+    let mut points_map = points_from(points_data, oldest_time)?;
+    // Self-clean the moment a window closes, not just when it times out.
+    retain_live_pids(&mut points_map);
+    Ok(points_map)
 }
 
 /// Parse a string into a PointsMap object of unexpired points.
 ///
-/// This removes all expired points from each pid's vec of points, and
-/// then removes any pids that no longer non-expired points. In other
-/// words, if you wait long enough between calls, this will eventually
-/// return an empty mapping.
-fn points_from(points_data: String, oldest_time: DateTime<Utc>) -> PointsMap {
-    let mut points_map: PointsMap = serde_yaml::from_str(&points_data).unwrap();
-
-    // Get rid of expired points.
-    for points in points_map.values_mut() {
-        points.retain(|point| point.added >= oldest_time);
+/// This removes all expired points from each pid's stacks, and then
+/// removes any pids whose stacks are empty afterward. Named bookmarks
+/// never expire, so if you wait long enough between calls, only the pids
+/// that hold bookmarks remain.
+fn points_from(
+    points_data: String,
+    oldest_time: DateTime<Utc>,
+) -> Result<PointsMap, JumpError> {
+    // An empty file (nothing saved yet) is an empty map, not a parse error.
+    let mut points_map: PointsMap = if points_data.trim().is_empty() {
+        PointsMap::new()
+    } else {
+        serde_yaml::from_str(&points_data)?
+    };
+
+    // Get rid of expired points in both stacks. Named bookmarks live in
+    // their own set and are intentional, so they never decay.
+    for stacks in points_map.values_mut() {
+        stacks.back.retain(|point| point.added >= oldest_time);
+        stacks.forward.retain(|point| point.added >= oldest_time);
     }
-    // Get rid of point vecs that are empty after pruning.
-    points_map.retain(|_, points| !points.is_empty());
+    // Get rid of stacks that are empty after pruning.
+    points_map.retain(|_, stacks| !stacks.is_empty());
 
-    points_map
+    Ok(points_map)
 }
 
 /// Store the PointsMap to the points file.
-pub fn save_points(points_path: &Path, points_map: PointsMap) {
-    // Get rid of empty point vecs.
-    let mut points_map = points_map.clone();
-    points_map.retain(|_, points| !points.is_empty());
-    let points_data = serde_yaml::to_string(&points_map).unwrap();
-    let _ = write(points_path, points_data);
+pub fn save_points(points_path: &Path, points_map: PointsMap) -> Result<(), JumpError> {
+    // Get rid of empty stacks.
+    let mut points_map = points_map;
+    points_map.retain(|_, stacks| !stacks.is_empty());
+    let points_data = serde_yaml::to_string(&points_map)?;
+    write(points_path, points_data)?;
+    Ok(())
 }
 
 #[cfg(test)]
@@ -138,6 +362,20 @@ mod tests {
     use chrono::prelude::*;
 
     const SAVED_POINTS: &str = "\
+123:
+  back:
+  - filename: /tmp/foo
+    line: 9
+    column: 42
+    added: 2023-10-03T07:59:59Z
+  - filename: /tmp/bar
+    line: 17
+    column: 23
+    added: 2023-10-03T08:00:00Z
+    ";
+
+    // The original on-disk shape, before the forward stack existed.
+    const LEGACY_POINTS: &str = "\
 123:
 - filename: /tmp/foo
   line: 9
@@ -149,28 +387,39 @@ mod tests {
   added: 2023-10-03T08:00:00Z
     ";
 
+    fn foo() -> JumpPoint {
+        JumpPoint {
+            filename: "/tmp/foo".to_string(),
+            line: 9,
+            column: 42,
+            added: Utc.with_ymd_and_hms(2023, 10, 3, 7, 59, 59).unwrap(),
+            label: None,
+        }
+    }
+
+    fn bar() -> JumpPoint {
+        JumpPoint {
+            filename: "/tmp/bar".to_string(),
+            line: 17,
+            column: 23,
+            added: Utc.with_ymd_and_hms(2023, 10, 3, 8, 0, 0).unwrap(),
+            label: None,
+        }
+    }
+
     #[test]
     fn all_points_are_current() {
         let expiration = Utc.with_ymd_and_hms(2023, 10, 3, 7, 59, 59).unwrap();
 
-        let points_map = points_from(SAVED_POINTS.to_string(), expiration);
+        let points_map = points_from(SAVED_POINTS.to_string(), expiration).unwrap();
 
         assert!(points_map.eq(&HashMap::from([(
             123,
-            vec![
-                JumpPoint {
-                    filename: "/tmp/foo".to_string(),
-                    line: 9,
-                    column: 42,
-                    added: Utc.with_ymd_and_hms(2023, 10, 3, 7, 59, 59).unwrap()
-                },
-                JumpPoint {
-                    filename: "/tmp/bar".to_string(),
-                    line: 17,
-                    column: 23,
-                    added: Utc.with_ymd_and_hms(2023, 10, 3, 8, 0, 0).unwrap()
-                },
-            ]
+            JumpStacks {
+                back: vec![foo(), bar()],
+                forward: vec![],
+                bookmarks: HashMap::new(),
+            }
         )])));
     }
 
@@ -178,16 +427,15 @@ mod tests {
     fn some_points_are_current() {
         let expiration = Utc.with_ymd_and_hms(2023, 10, 3, 8, 0, 0).unwrap();
 
-        let points_map = points_from(SAVED_POINTS.to_string(), expiration);
+        let points_map = points_from(SAVED_POINTS.to_string(), expiration).unwrap();
 
         assert!(points_map.eq(&HashMap::from([(
             123,
-            vec![JumpPoint {
-                filename: "/tmp/bar".to_string(),
-                line: 17,
-                column: 23,
-                added: Utc.with_ymd_and_hms(2023, 10, 3, 8, 0, 0).unwrap()
-            },]
+            JumpStacks {
+                back: vec![bar()],
+                forward: vec![],
+                bookmarks: HashMap::new(),
+            }
         )])));
     }
 
@@ -195,8 +443,69 @@ mod tests {
     fn no_points_are_current() {
         let expiration = Utc.with_ymd_and_hms(2023, 10, 3, 8, 0, 1).unwrap();
 
-        let points_map = points_from(SAVED_POINTS.to_string(), expiration);
+        let points_map = points_from(SAVED_POINTS.to_string(), expiration).unwrap();
 
         assert!(points_map.eq(&HashMap::new()));
     }
+
+    #[test]
+    fn legacy_list_loads_as_back_stack() {
+        let expiration = Utc.with_ymd_and_hms(2023, 10, 3, 7, 59, 59).unwrap();
+
+        let points_map = points_from(LEGACY_POINTS.to_string(), expiration).unwrap();
+
+        assert!(points_map.eq(&HashMap::from([(
+            123,
+            JumpStacks {
+                back: vec![foo(), bar()],
+                forward: vec![],
+                bookmarks: HashMap::new(),
+            }
+        )])));
+    }
+
+    // A named bookmark that's well past the expiration, alongside an
+    // anonymous back-stack point old enough to decay.
+    const LABELED_POINTS: &str = "\
+123:
+  back:
+  - filename: /tmp/foo
+    line: 9
+    column: 42
+    added: 2023-10-03T07:59:59Z
+  bookmarks:
+    home:
+      filename: /tmp/marked
+      line: 1
+      column: 1
+      added: 2023-10-03T07:00:00Z
+      label: home
+    ";
+
+    #[test]
+    fn bookmarks_never_expire() {
+        // Expiration after both points were recorded: the anonymous one
+        // decays, the bookmark stays.
+        let expiration = Utc.with_ymd_and_hms(2023, 10, 3, 8, 0, 1).unwrap();
+
+        let points_map = points_from(LABELED_POINTS.to_string(), expiration).unwrap();
+
+        assert!(points_map.eq(&HashMap::from([(
+            123,
+            JumpStacks {
+                back: vec![],
+                forward: vec![],
+                bookmarks: HashMap::from([(
+                    "home".to_string(),
+                    JumpPoint {
+                        filename: "/tmp/marked".to_string(),
+                        line: 1,
+                        column: 1,
+                        added: Utc.with_ymd_and_hms(2023, 10, 3, 7, 0, 0).unwrap(),
+                        label: Some("home".to_string()),
+                    }
+                )]),
+            }
+        )])));
+    }
 }